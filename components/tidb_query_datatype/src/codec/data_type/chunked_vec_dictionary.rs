@@ -0,0 +1,304 @@
+// Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! A dictionary-encoded `ChunkedVec<Bytes>`, modeled on Arrow's dictionary
+//! encoding: a deduplicated pool of distinct values plus a vector of `Int`
+//! indices into that pool. This is a significant memory and comparison-speed
+//! win for low-cardinality string columns (status codes, enum-like text),
+//! at the cost of an extra hash lookup on every push.
+//!
+//! Like [`super::ChunkedVecBytes`], the null bitmap and the index slot are
+//! always written together: a `None` push still consumes an index slot (its
+//! value is unspecified) alongside the corresponding cleared bitmap bit.
+
+use std::collections::HashMap;
+
+use super::{BitVec, Bytes, BytesRef, ChunkRef, ChunkedVec, ChunkedVecBytes, Int, UnsafeRefInto};
+
+/// Above this many distinct values, a [`ChunkedVecDictionary`] stops paying
+/// off: the dictionary no longer fits comfortably in cache and per-value
+/// savings shrink, so `chunked_push` transparently falls back to a plain
+/// [`ChunkedVecBytes`] representation instead of growing the dictionary
+/// further.
+pub const DEFAULT_CARDINALITY_THRESHOLD: usize = 4096;
+
+#[derive(Debug, PartialEq, Clone)]
+enum Storage {
+    Dictionary {
+        /// Deduplicated value pool, indexed by `Int`.
+        values: Vec<Bytes>,
+        /// value -> index into `values`, used to dedup on push.
+        index_of: HashMap<Bytes, Int>,
+        /// Per-row index into `values`; meaningless where `bitmap` is unset.
+        indices: Vec<Int>,
+    },
+    /// Cardinality exceeded the threshold; behaves like a plain `Bytes`
+    /// column from here on.
+    PlainFallback(ChunkedVecBytes),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ChunkedVecDictionary {
+    bitmap: BitVec,
+    storage: Storage,
+    cardinality_threshold: usize,
+}
+
+impl ChunkedVecDictionary {
+    pub fn with_cardinality_threshold(capacity: usize, cardinality_threshold: usize) -> Self {
+        Self {
+            bitmap: BitVec::with_capacity(capacity),
+            storage: Storage::Dictionary {
+                values: Vec::new(),
+                index_of: HashMap::new(),
+                indices: Vec::with_capacity(capacity),
+            },
+            cardinality_threshold,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bitmap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether this column is still dictionary-encoded, i.e. has not yet
+    /// fallen back to a plain representation. Aggregation operators can use
+    /// this to decide whether it's worth comparing by dictionary index
+    /// instead of by value.
+    pub fn is_dictionary_encoded(&self) -> bool {
+        matches!(self.storage, Storage::Dictionary { .. })
+    }
+
+    pub fn cardinality_threshold(&self) -> usize {
+        self.cardinality_threshold
+    }
+
+    pub fn bit_vec(&self) -> &BitVec {
+        &self.bitmap
+    }
+
+    pub fn get(&self, idx: usize) -> Option<BytesRef<'_>> {
+        if !self.bitmap.get(idx) {
+            return None;
+        }
+        Some(match &self.storage {
+            Storage::Dictionary { values, indices, .. } => values[indices[idx] as usize].as_slice(),
+            Storage::PlainFallback(v) => v.get_option_ref(idx).unwrap(),
+        })
+    }
+
+    fn fall_back_to_plain(&mut self) {
+        let (values, indices) = match &self.storage {
+            Storage::Dictionary { values, indices, .. } => (values, indices),
+            Storage::PlainFallback(_) => return,
+        };
+        let mut plain = ChunkedVecBytes::chunked_with_capacity(self.bitmap.len());
+        for idx in 0..self.bitmap.len() {
+            if self.bitmap.get(idx) {
+                plain.chunked_push(Some(values[indices[idx] as usize].clone()));
+            } else {
+                plain.chunked_push(None);
+            }
+        }
+        self.storage = Storage::PlainFallback(plain);
+    }
+}
+
+impl ChunkedVec<Bytes> for ChunkedVecDictionary {
+    fn chunked_with_capacity(capacity: usize) -> Self {
+        Self::with_cardinality_threshold(capacity, DEFAULT_CARDINALITY_THRESHOLD)
+    }
+
+    fn chunked_push(&mut self, value: Option<Bytes>) {
+        self.bitmap.push(value.is_some());
+        match &mut self.storage {
+            Storage::Dictionary {
+                values,
+                index_of,
+                indices,
+            } => match value {
+                None => indices.push(0),
+                Some(v) => {
+                    let idx = *index_of.entry(v.clone()).or_insert_with(|| {
+                        values.push(v);
+                        (values.len() - 1) as Int
+                    });
+                    indices.push(idx);
+                    if values.len() > self.cardinality_threshold {
+                        self.fall_back_to_plain();
+                    }
+                }
+            },
+            Storage::PlainFallback(v) => v.chunked_push(value),
+        }
+    }
+}
+
+impl<'a> ChunkRef<'a, BytesRef<'a>> for &'a ChunkedVecDictionary {
+    fn get_option_ref(self, idx: usize) -> Option<BytesRef<'a>> {
+        self.get(idx)
+    }
+
+    fn get_bit_vec(self) -> &'a BitVec {
+        &self.bitmap
+    }
+
+    fn phantom_data(self) -> Option<BytesRef<'a>> {
+        None
+    }
+}
+
+impl<'a> UnsafeRefInto<&'static ChunkedVecDictionary> for &'a ChunkedVecDictionary {
+    unsafe fn unsafe_into(self) -> &'static ChunkedVecDictionary {
+        std::mem::transmute(self)
+    }
+}
+
+/// A `Bytes` chunk that is either plain or dictionary-encoded, for callers
+/// that want to opt a column into [`ChunkedVecDictionary`] without giving up
+/// the plain representation for everything else.
+///
+/// This is **not** currently `VectorValue::Bytes`'s payload type: that variant
+/// is declared in `vector.rs`, which this series never touches, and is
+/// assumed (like the rest of `VectorValue`) to still hold a plain
+/// `ChunkedVecBytes`. Making dictionary encoding reachable from
+/// evaluator/coprocessor code — e.g. by changing `VectorValue::Bytes` to hold
+/// this type, or by adding a dedicated `VectorValue::Dictionary` variant —
+/// needs a change to `vector.rs` itself, which is out of scope here. Until
+/// then, `BytesChunk` is a standalone, directly-constructible/-tested type
+/// (see [`ChunkedVecDictionary`] for the encoding it wraps), not something
+/// any existing eval path produces.
+#[derive(Debug, PartialEq, Clone)]
+pub enum BytesChunk {
+    Plain(ChunkedVecBytes),
+    Dictionary(ChunkedVecDictionary),
+}
+
+impl BytesChunk {
+    pub fn with_dictionary_encoding(capacity: usize) -> Self {
+        BytesChunk::Dictionary(ChunkedVecDictionary::chunked_with_capacity(capacity))
+    }
+
+    /// Whether this column is currently dictionary-encoded, i.e. was built
+    /// via [`BytesChunk::with_dictionary_encoding`] and has not since fallen
+    /// back to plain storage.
+    pub fn is_dictionary_encoded(&self) -> bool {
+        matches!(self, BytesChunk::Dictionary(v) if v.is_dictionary_encoded())
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            BytesChunk::Plain(v) => v.len(),
+            BytesChunk::Dictionary(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, idx: usize) -> Option<BytesRef<'_>> {
+        match self {
+            BytesChunk::Plain(v) => v.get_option_ref(idx),
+            BytesChunk::Dictionary(v) => v.get(idx),
+        }
+    }
+}
+
+impl ChunkedVec<Bytes> for BytesChunk {
+    fn chunked_with_capacity(capacity: usize) -> Self {
+        BytesChunk::Plain(ChunkedVecBytes::chunked_with_capacity(capacity))
+    }
+
+    fn chunked_push(&mut self, value: Option<Bytes>) {
+        match self {
+            BytesChunk::Plain(v) => v.chunked_push(value),
+            BytesChunk::Dictionary(v) => v.chunked_push(value),
+        }
+    }
+}
+
+impl<'a> ChunkRef<'a, BytesRef<'a>> for &'a BytesChunk {
+    fn get_option_ref(self, idx: usize) -> Option<BytesRef<'a>> {
+        self.get(idx)
+    }
+
+    fn get_bit_vec(self) -> &'a BitVec {
+        match self {
+            BytesChunk::Plain(v) => v.get_bit_vec(),
+            BytesChunk::Dictionary(v) => v.get_bit_vec(),
+        }
+    }
+
+    fn phantom_data(self) -> Option<BytesRef<'a>> {
+        None
+    }
+}
+
+impl<'a> UnsafeRefInto<&'static BytesChunk> for &'a BytesChunk {
+    unsafe fn unsafe_into(self) -> &'static BytesChunk {
+        std::mem::transmute(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dictionary_dedup_and_lookup() {
+        let mut v = ChunkedVecDictionary::chunked_with_capacity(4);
+        v.chunked_push(Some(b"ok".to_vec()));
+        v.chunked_push(None);
+        v.chunked_push(Some(b"ok".to_vec()));
+        v.chunked_push(Some(b"error".to_vec()));
+
+        assert_eq!(v.get(0), Some(b"ok".as_ref()));
+        assert_eq!(v.get(1), None);
+        assert_eq!(v.get(2), Some(b"ok".as_ref()));
+        assert_eq!(v.get(3), Some(b"error".as_ref()));
+        assert!(v.is_dictionary_encoded());
+        match &v.storage {
+            Storage::Dictionary { values, .. } => assert_eq!(values.len(), 2),
+            Storage::PlainFallback(_) => panic!("expected dictionary storage"),
+        }
+    }
+
+    #[test]
+    fn test_dictionary_falls_back_above_threshold() {
+        let mut v = ChunkedVecDictionary::with_cardinality_threshold(8, 2);
+        v.chunked_push(Some(b"a".to_vec()));
+        v.chunked_push(Some(b"b".to_vec()));
+        v.chunked_push(Some(b"c".to_vec()));
+
+        assert!(!v.is_dictionary_encoded());
+        assert_eq!(v.get(0), Some(b"a".as_ref()));
+        assert_eq!(v.get(1), Some(b"b".as_ref()));
+        assert_eq!(v.get(2), Some(b"c".as_ref()));
+    }
+
+    #[test]
+    fn test_bytes_chunk_plain_is_default() {
+        let mut v = BytesChunk::chunked_with_capacity(2);
+        v.chunked_push(Some(b"ok".to_vec()));
+        v.chunked_push(None);
+
+        assert!(!v.is_dictionary_encoded());
+        assert_eq!(v.get(0), Some(b"ok".as_ref()));
+        assert_eq!(v.get(1), None);
+    }
+
+    #[test]
+    fn test_bytes_chunk_with_dictionary_encoding() {
+        let mut v = BytesChunk::with_dictionary_encoding(2);
+        v.chunked_push(Some(b"ok".to_vec()));
+        v.chunked_push(Some(b"ok".to_vec()));
+
+        assert!(v.is_dictionary_encoded());
+        assert_eq!(v.get(0), Some(b"ok".as_ref()));
+        assert_eq!(v.get(1), Some(b"ok".as_ref()));
+    }
+}
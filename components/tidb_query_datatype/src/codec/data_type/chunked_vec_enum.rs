@@ -0,0 +1,105 @@
+// Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! `ChunkedVec<Enum>`: stores the compact `u64` index per row plus a single
+//! shared name table, rather than repeating each member's display name on
+//! every row.
+//!
+//! Not yet reachable from `VectorValue` — see the status note on
+//! [`super::enums`].
+
+use super::enums::{Enum, EnumRef, NameTable};
+use super::{BitVec, ChunkRef, ChunkedVec, UnsafeRefInto};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ChunkedVecEnum {
+    bitmap: BitVec,
+    values: Vec<u64>,
+    names: NameTable,
+}
+
+impl ChunkedVecEnum {
+    pub fn with_name_table(capacity: usize, names: NameTable) -> Self {
+        Self {
+            bitmap: BitVec::with_capacity(capacity),
+            values: Vec::with_capacity(capacity),
+            names,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bitmap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, idx: usize) -> Option<EnumRef<'_>> {
+        if !self.bitmap.get(idx) {
+            return None;
+        }
+        Some(EnumRef::new(&self.names, self.values[idx]))
+    }
+}
+
+impl ChunkedVec<Enum> for ChunkedVecEnum {
+    fn chunked_with_capacity(capacity: usize) -> Self {
+        Self::with_name_table(capacity, NameTable::default())
+    }
+
+    fn chunked_push(&mut self, value: Option<Enum>) {
+        self.bitmap.push(value.is_some());
+        match value {
+            None => self.values.push(0),
+            Some(v) => {
+                // `chunked_with_capacity` has no way to receive the column's
+                // name table up front, so the first value pushed through the
+                // generic `ChunkedVec` path is what seeds it; every `Enum` in
+                // a single column carries the same table, so later pushes
+                // just overwrite it with that same table (a cheap `Arc`
+                // clone); the tables are not compared.
+                self.names = v.names().clone();
+                self.values.push(v.value());
+            }
+        }
+    }
+}
+
+impl<'a> ChunkRef<'a, EnumRef<'a>> for &'a ChunkedVecEnum {
+    fn get_option_ref(self, idx: usize) -> Option<EnumRef<'a>> {
+        self.get(idx)
+    }
+
+    fn get_bit_vec(self) -> &'a BitVec {
+        &self.bitmap
+    }
+
+    fn phantom_data(self) -> Option<EnumRef<'a>> {
+        None
+    }
+}
+
+impl<'a> UnsafeRefInto<&'static ChunkedVecEnum> for &'a ChunkedVecEnum {
+    unsafe fn unsafe_into(self) -> &'static ChunkedVecEnum {
+        std::mem::transmute(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_chunked_push_recovers_name_table() {
+        let names: NameTable = Arc::new(vec![b"a".to_vec(), b"b".to_vec()]);
+        let mut v = ChunkedVecEnum::chunked_with_capacity(3);
+        v.chunked_push(Some(Enum::new(names.clone(), 2)));
+        v.chunked_push(None);
+        v.chunked_push(Some(Enum::new(names, 1)));
+
+        assert_eq!(v.get(0).unwrap().name(), b"b");
+        assert_eq!(v.get(1), None);
+        assert_eq!(v.get(2).unwrap().name(), b"a");
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! `ChunkedVec<Set>`: stores the compact `u64` bitmask per row plus a single
+//! shared table of member names; the comma-joined display string is only
+//! materialized when a row is actually read.
+//!
+//! Not yet reachable from `VectorValue` — see the status note on
+//! [`super::enums`].
+
+use super::enums::{NameTable, Set, SetRef};
+use super::{BitVec, ChunkRef, ChunkedVec, UnsafeRefInto};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ChunkedVecSet {
+    bitmap: BitVec,
+    values: Vec<u64>,
+    names: NameTable,
+}
+
+impl ChunkedVecSet {
+    pub fn with_name_table(capacity: usize, names: NameTable) -> Self {
+        Self {
+            bitmap: BitVec::with_capacity(capacity),
+            values: Vec::with_capacity(capacity),
+            names,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bitmap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, idx: usize) -> Option<SetRef<'_>> {
+        if !self.bitmap.get(idx) {
+            return None;
+        }
+        Some(SetRef::new(&self.names, self.values[idx]))
+    }
+}
+
+impl ChunkedVec<Set> for ChunkedVecSet {
+    fn chunked_with_capacity(capacity: usize) -> Self {
+        Self::with_name_table(capacity, NameTable::default())
+    }
+
+    fn chunked_push(&mut self, value: Option<Set>) {
+        self.bitmap.push(value.is_some());
+        match value {
+            None => self.values.push(0),
+            Some(v) => {
+                // Same reasoning as `ChunkedVecEnum::chunked_push`: the name
+                // table has to ride along on each pushed `Set` since
+                // `chunked_with_capacity` can't receive it up front.
+                self.names = v.names().clone();
+                self.values.push(v.value());
+            }
+        }
+    }
+}
+
+impl<'a> ChunkRef<'a, SetRef<'a>> for &'a ChunkedVecSet {
+    fn get_option_ref(self, idx: usize) -> Option<SetRef<'a>> {
+        self.get(idx)
+    }
+
+    fn get_bit_vec(self) -> &'a BitVec {
+        &self.bitmap
+    }
+
+    fn phantom_data(self) -> Option<SetRef<'a>> {
+        None
+    }
+}
+
+impl<'a> UnsafeRefInto<&'static ChunkedVecSet> for &'a ChunkedVecSet {
+    unsafe fn unsafe_into(self) -> &'static ChunkedVecSet {
+        std::mem::transmute(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_chunked_push_recovers_name_table() {
+        let names: NameTable = Arc::new(vec![b"a".to_vec(), b"b".to_vec()]);
+        let mut v = ChunkedVecSet::chunked_with_capacity(2);
+        v.chunked_push(Some(Set::new(names.clone(), 0b11)));
+        v.chunked_push(None);
+
+        assert_eq!(v.get(0).unwrap().name(), b"a,b");
+        assert_eq!(v.get(1), None);
+    }
+}
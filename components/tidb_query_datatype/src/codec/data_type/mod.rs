@@ -1,12 +1,18 @@
 // Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
 
+mod arrow;
 mod bit_vec;
 mod chunked_vec_bytes;
 mod chunked_vec_common;
+mod chunked_vec_dictionary;
+mod chunked_vec_enum;
 mod chunked_vec_json;
+mod chunked_vec_set;
 mod chunked_vec_sized;
+mod enums;
 mod logical_rows;
 mod scalar;
+mod serde;
 mod vector;
 pub use logical_rows::{LogicalRows, BATCH_MAX_SIZE, IDENTICAL_LOGICAL_ROWS};
 
@@ -15,11 +21,18 @@ pub type Int = i64;
 pub type Real = ordered_float::NotNan<f64>;
 pub type Bytes = Vec<u8>;
 pub type BytesRef<'a> = &'a [u8];
-pub use crate::codec::mysql::{json::JsonRef, Decimal, Duration, Json, JsonType, Time as DateTime};
+pub use crate::codec::mysql::{
+    json::{JsonRef, JSON_LITERAL_TRUE},
+    Decimal, Duration, Json, JsonType, Time as DateTime,
+};
 pub use bit_vec::{BitAndIterator, BitVec};
 pub use chunked_vec_bytes::{BytesGuard, BytesWriter, ChunkedVecBytes, PartialBytesWriter};
+pub use chunked_vec_dictionary::{BytesChunk, ChunkedVecDictionary, DEFAULT_CARDINALITY_THRESHOLD};
+pub use chunked_vec_enum::ChunkedVecEnum;
 pub use chunked_vec_json::ChunkedVecJson;
+pub use chunked_vec_set::ChunkedVecSet;
 pub use chunked_vec_sized::ChunkedVecSized;
+pub use enums::{Enum, EnumRef, NameTable, Set, SetRef};
 
 // Dynamic eval types.
 pub use self::scalar::{ScalarValue, ScalarValueRef};
@@ -29,7 +42,7 @@ use crate::EvalType;
 
 use crate::codec::convert::ConvertTo;
 use crate::expr::EvalContext;
-use tidb_query_common::error::Result;
+use tidb_query_common::error::{Error, Result};
 
 /// A trait of evaluating current concrete eval type into a MySQL logic value, represented by
 /// Rust's `bool` type.
@@ -86,9 +99,54 @@ where
 }
 
 impl<'a> AsMySQLBool for JsonRef<'a> {
+    fn as_mysql_bool(&self, context: &mut EvalContext) -> Result<bool> {
+        // Mirrors TiDB's JSON-to-bool cast rules, see pingcap/tidb#9593: a literal
+        // `true` is truthy, `false`/`null` are not; numbers reuse the `Int`/`Real`
+        // truthiness (erroring on NaN the same way `Real` would refuse to represent
+        // one); strings fall back to the existing `BytesRef` rules; arrays and
+        // objects are always truthy.
+        Ok(match self.get_type() {
+            JsonType::Literal => self.as_literal()? == JSON_LITERAL_TRUE,
+            JsonType::I64 => self.as_i64()?.as_mysql_bool(context)?,
+            JsonType::U64 => self.as_u64()? != 0,
+            JsonType::Double => Real::new(self.as_f64()?)
+                .map_err(|_| Error::overflow("DOUBLE", format!("{:?}", self)))?
+                .as_mysql_bool(context)?,
+            JsonType::String => self.as_str()?.as_bytes().as_mysql_bool(context)?,
+            JsonType::Object | JsonType::Array => true,
+        })
+    }
+}
+
+impl<'a> AsMySQLBool for EnumRef<'a> {
+    #[inline]
+    fn as_mysql_bool(&self, _context: &mut EvalContext) -> Result<bool> {
+        Ok(self.value() != 0)
+    }
+}
+
+impl<'a> AsMySQLBool for SetRef<'a> {
+    #[inline]
     fn as_mysql_bool(&self, _context: &mut EvalContext) -> Result<bool> {
-        // TODO: This logic is not correct. See pingcap/tidb#9593
-        Ok(false)
+        Ok(self.value() != 0)
+    }
+}
+
+impl<'a> AsMySQLBool for Option<EnumRef<'a>> {
+    fn as_mysql_bool(&self, context: &mut EvalContext) -> Result<bool> {
+        match self {
+            None => Ok(false),
+            Some(ref v) => v.as_mysql_bool(context),
+        }
+    }
+}
+
+impl<'a> AsMySQLBool for Option<SetRef<'a>> {
+    fn as_mysql_bool(&self, context: &mut EvalContext) -> Result<bool> {
+        match self {
+            None => Ok(false),
+            Some(ref v) => v.as_mysql_bool(context),
+        }
     }
 }
 
@@ -237,6 +295,8 @@ impl_evaluable_ret! { Bytes, ChunkedVecBytes }
 impl_evaluable_ret! { DateTime, ChunkedVecSized<Self> }
 impl_evaluable_ret! { Duration, ChunkedVecSized<Self> }
 impl_evaluable_ret! { Json, ChunkedVecJson }
+// `Enum`/`Set` intentionally have no `impl_evaluable_ret!` here — see the
+// "Status: partial" note on `enums` for why and what's left.
 
 pub trait EvaluableRef<'a>: Clone + std::fmt::Debug + Send + Sync {
     const EVAL_TYPE: EvalType;
@@ -396,6 +456,80 @@ impl<'a> EvaluableRef<'a> for JsonRef<'a> {
     }
 }
 
+impl<'a> UnsafeRefInto<EnumRef<'static>> for EnumRef<'a> {
+    unsafe fn unsafe_into(self) -> EnumRef<'static> {
+        std::mem::transmute(self)
+    }
+}
+
+impl<'a> UnsafeRefInto<SetRef<'static>> for SetRef<'a> {
+    unsafe fn unsafe_into(self) -> SetRef<'static> {
+        std::mem::transmute(self)
+    }
+}
+
+// `EvaluableRef` for `EnumRef`/`SetRef` has no impl here either, for the same
+// reason — see the "Status: partial" note on `enums`.
+
+/// A representative non-null value for each eval type, for round-trip tests
+/// (Arrow, serde) that want to exercise real data rather than only `None` —
+/// a `None`-only round trip can't catch a broken codec or a mis-mapped
+/// buffer for the actual value.
+#[cfg(test)]
+pub(crate) trait SampleValue: EvaluableRet {
+    fn sample_value() -> Self;
+}
+
+#[cfg(test)]
+impl SampleValue for Int {
+    fn sample_value() -> Self {
+        42
+    }
+}
+
+#[cfg(test)]
+impl SampleValue for Real {
+    fn sample_value() -> Self {
+        Real::new(1.5).unwrap()
+    }
+}
+
+#[cfg(test)]
+impl SampleValue for Bytes {
+    fn sample_value() -> Self {
+        b"hello".to_vec()
+    }
+}
+
+#[cfg(test)]
+impl SampleValue for Decimal {
+    fn sample_value() -> Self {
+        Decimal::from(42)
+    }
+}
+
+#[cfg(test)]
+impl SampleValue for DateTime {
+    fn sample_value() -> Self {
+        DateTime::parse_datetime(&mut EvalContext::default(), "2000-01-01 00:00:00", 0, false)
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+impl SampleValue for Duration {
+    fn sample_value() -> Self {
+        Duration::parse(&mut EvalContext::default(), "10:00:00", 0).unwrap()
+    }
+}
+
+#[cfg(test)]
+impl SampleValue for Json {
+    fn sample_value() -> Self {
+        Json::from_i64(42).unwrap()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -483,4 +617,39 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_json_as_bool() {
+        let tests: Vec<(Result<Json>, Option<bool>)> = vec![
+            (Json::from_bool(true), Some(true)),
+            (Json::from_bool(false), Some(false)),
+            (Ok(Json::none().unwrap()), Some(false)),
+            (Json::from_i64(0), Some(false)),
+            (Json::from_i64(42), Some(true)),
+            (Json::from_i64(-1), Some(true)),
+            (Json::from_u64(0), Some(false)),
+            (Json::from_u64(42), Some(true)),
+            (Json::from_f64(0.0), Some(false)),
+            (Json::from_f64(0.5), Some(true)),
+            (Json::from_f64(f64::NAN), None),
+            (Json::from_string(String::new()), Some(false)),
+            (Json::from_string("0".to_string()), Some(false)),
+            (Json::from_array(vec![]), Some(true)),
+            (Json::from_object(std::collections::BTreeMap::new()), Some(true)),
+        ];
+
+        let mut ctx = EvalContext::default();
+        for (i, (json, expect)) in tests.into_iter().enumerate() {
+            match json {
+                Ok(j) => {
+                    let r = j.as_ref().as_mysql_bool(&mut ctx);
+                    match expect {
+                        Some(val) => assert_eq!(r.unwrap(), val, "index: {}", i),
+                        None => assert!(r.is_err(), "index: {} should not be converted", i),
+                    }
+                }
+                Err(_) => assert!(expect.is_none(), "index: {} construction should fail", i),
+            }
+        }
+    }
 }